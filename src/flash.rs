@@ -0,0 +1,120 @@
+use volatile::Volatile;
+use bit_field::BitField;
+
+use core::sync::atomic::{AtomicBool,Ordering};
+
+// Field order matches the physical register layout: within each FCCOB
+// group the bytes are addressed in reverse (FCCOB3 before FCCOB0), which
+// is how the FTFL maps them in memory.
+#[repr(C,packed)]
+struct FtflRegs {
+    fstat: Volatile<u8>,
+    fcnfg: Volatile<u8>,
+    fsec: Volatile<u8>,
+    fopt: Volatile<u8>,
+    fccob3: Volatile<u8>,
+    fccob2: Volatile<u8>,
+    fccob1: Volatile<u8>,
+    fccob0: Volatile<u8>,
+    fccob7: Volatile<u8>,
+    fccob6: Volatile<u8>,
+    fccob5: Volatile<u8>,
+    fccob4: Volatile<u8>,
+}
+
+const SECTOR_SIZE: u32 = 2048;
+
+const CMD_PROGRAM_LONGWORD: u8 = 0x06;
+const CMD_ERASE_SECTOR: u8 = 0x09;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Error {
+    /// The command targeted a protected flash range (FPVIOL).
+    ProtectionViolation,
+    /// The command sequence itself was invalid (ACCERR).
+    AccessError,
+}
+
+/// The Kinetis FTFL flash controller, for reading program/erase status and
+/// issuing self-programming commands.
+///
+/// Erasing or programming blocks until the hardware command completes, and
+/// while it's in progress the flash block being modified cannot be read --
+/// which includes fetching instructions, if this code were itself running
+/// from flash. The polling loop is therefore placed in `.ramfunc` and must
+/// stay there; don't call it, or add to it, without keeping that in mind.
+pub struct Flash {
+    reg: &'static mut FtflRegs
+}
+
+static FLASH_INIT: AtomicBool = AtomicBool::new(false);
+
+impl Flash {
+    pub unsafe fn new() -> Flash {
+        let was_init = FLASH_INIT.swap(true, Ordering::SeqCst);
+        if was_init {
+            panic!("Cannot initialize Flash: It's already active");
+        }
+        let reg = &mut *(0x40020000 as *mut FtflRegs);
+        Flash { reg }
+    }
+
+    /// Erases the 2KB sector starting at `addr`. Panics if `addr` is not
+    /// 2KB-aligned.
+    pub fn erase_sector(&mut self, addr: u32) -> Result<(), Error> {
+        if addr % SECTOR_SIZE != 0 {
+            panic!("Flash sector address {:#010x} is not 2KB-aligned", addr);
+        }
+
+        self.stage_command(CMD_ERASE_SECTOR, addr);
+        self.launch_and_poll()
+    }
+
+    /// Programs a single 32-bit word. `addr` must fall in an already-erased
+    /// region; the FTFL can only flip bits from 1 to 0.
+    pub fn program_longword(&mut self, addr: u32, data: u32) -> Result<(), Error> {
+        if addr % 4 != 0 {
+            panic!("Flash program address {:#010x} is not word-aligned", addr);
+        }
+
+        self.stage_command(CMD_PROGRAM_LONGWORD, addr);
+        self.reg.fccob4.write(data.get_bits(0..8) as u8);
+        self.reg.fccob5.write(data.get_bits(8..16) as u8);
+        self.reg.fccob6.write(data.get_bits(16..24) as u8);
+        self.reg.fccob7.write(data.get_bits(24..32) as u8);
+
+        self.launch_and_poll()
+    }
+
+    fn stage_command(&mut self, command: u8, addr: u32) {
+        self.reg.fccob0.write(command);
+        self.reg.fccob1.write(addr.get_bits(16..24) as u8);
+        self.reg.fccob2.write(addr.get_bits(8..16) as u8);
+        self.reg.fccob3.write(addr.get_bits(0..8) as u8);
+    }
+
+    /// Launches the command already staged in FCCOB by clearing CCIF, then
+    /// polls FSTAT until the hardware reports completion.
+    #[link_section = ".ramfunc"]
+    fn launch_and_poll(&mut self) -> Result<(), Error> {
+        // Writing 1s to CCIF/ACCERR/FPVIOL clears any stale error state and,
+        // for CCIF specifically, launches the staged command.
+        self.reg.fstat.write(0xF0);
+
+        while !self.reg.fstat.read().get_bit(7) {}
+
+        let status = self.reg.fstat.read();
+        if status.get_bit(4) || status.get_bit(5) {
+            self.reg.fstat.write(0x30);
+            return Err(if status.get_bit(4) { Error::ProtectionViolation } else { Error::AccessError });
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Flash {
+    fn drop(&mut self) {
+        FLASH_INIT.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,166 @@
+use core::arch::arm::__nop;
+
+use crate::port::{Gpio, Pin};
+use crate::time::Hertz;
+use embedded_hal::blocking::i2c;
+
+/// A software (bit-banged) I2C master, built from two GPIO pins configured
+/// as open-drain outputs. Useful on pins with no hardware I2C module, at
+/// the cost of needing the CPU for every bit.
+pub struct I2c<'a, 'b> {
+    sda: Gpio<'a>,
+    scl: Gpio<'b>,
+    half_bit_delay: u32,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Error {
+    /// The addressed device didn't pull SDA low for its ACK bit.
+    NoAck,
+}
+
+impl<'a, 'b> I2c<'a, 'b> {
+    /// Configures `sda`/`scl` as open-drain GPIOs and idles the bus (both
+    /// lines released high). `core_clock` is used to turn `bus_speed` into
+    /// a number of busy-wait cycles per half bit-period.
+    pub fn new(sda: Pin<'a>, scl: Pin<'b>, core_clock: Hertz, bus_speed: Hertz) -> I2c<'a, 'b> {
+        let mut sda = sda.make_gpio();
+        let mut scl = scl.make_gpio();
+
+        sda.set_open_drain(true);
+        scl.set_open_drain(true);
+        sda.output();
+        scl.output();
+        sda.high();
+        scl.high();
+
+        // Each bit takes two half-periods, and each half-period burns
+        // roughly one core clock cycle per `__nop`.
+        let half_bit_delay = core_clock.0 / (2 * bus_speed.0);
+
+        I2c { sda, scl, half_bit_delay }
+    }
+
+    fn delay(&self) {
+        for _ in 0..self.half_bit_delay {
+            unsafe {
+                __nop();
+            }
+        }
+    }
+
+    fn start(&mut self) {
+        self.sda.high();
+        self.scl.high();
+        self.delay();
+        self.sda.low();
+        self.delay();
+        self.scl.low();
+    }
+
+    fn stop(&mut self) {
+        self.sda.low();
+        self.delay();
+        self.scl.high();
+        self.delay();
+        self.sda.high();
+        self.delay();
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if bit {
+            self.sda.high();
+        } else {
+            self.sda.low();
+        }
+        self.delay();
+
+        self.scl.high();
+        // Clock stretching: a slow slave holds SCL low until it's ready.
+        while self.scl.is_low() {}
+        self.delay();
+
+        self.scl.low();
+    }
+
+    fn read_bit(&mut self) -> bool {
+        self.sda.high(); // Release SDA so the slave can drive it.
+        self.delay();
+
+        self.scl.high();
+        while self.scl.is_low() {}
+        let bit = self.sda.is_high();
+        self.delay();
+
+        self.scl.low();
+        bit
+    }
+
+    fn write_byte(&mut self, byte: u8) -> bool {
+        for i in (0..8).rev() {
+            self.write_bit(byte & (1 << i) != 0);
+        }
+        !self.read_bit() // ACK is SDA held low by the slave.
+    }
+
+    fn read_byte(&mut self, ack: bool) -> u8 {
+        let mut byte = 0;
+        for _ in 0..8 {
+            byte = (byte << 1) | (self.read_bit() as u8);
+        }
+        self.write_bit(!ack); // We ACK (pull low) all but the last byte.
+        byte
+    }
+
+    pub fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
+        self.start();
+
+        if !self.write_byte(addr << 1) {
+            self.stop();
+            return Err(Error::NoAck);
+        }
+
+        for &byte in bytes {
+            if !self.write_byte(byte) {
+                self.stop();
+                return Err(Error::NoAck);
+            }
+        }
+
+        self.stop();
+        Ok(())
+    }
+
+    pub fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        self.start();
+
+        if !self.write_byte((addr << 1) | 1) {
+            self.stop();
+            return Err(Error::NoAck);
+        }
+
+        let last = buffer.len().saturating_sub(1);
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            *slot = self.read_byte(i != last);
+        }
+
+        self.stop();
+        Ok(())
+    }
+}
+
+impl<'a, 'b> i2c::Write for I2c<'a, 'b> {
+    type Error = Error;
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
+        I2c::write(self, addr, bytes)
+    }
+}
+
+impl<'a, 'b> i2c::Read for I2c<'a, 'b> {
+    type Error = Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        I2c::read(self, addr, buffer)
+    }
+}
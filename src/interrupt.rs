@@ -0,0 +1,31 @@
+/// Runs `f` with interrupts disabled, then restores the previous PRIMASK
+/// state (rather than unconditionally re-enabling), so a critical section
+/// taken from inside another one doesn't prematurely open the window back
+/// up.
+pub fn free<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let primask: u32;
+    unsafe {
+        asm!("mrs {}, PRIMASK", out(reg) primask);
+        asm!("cpsid i");
+    }
+
+    let result = f();
+
+    if primask & 1 == 0 {
+        unsafe {
+            asm!("cpsie i");
+        }
+    }
+
+    result
+}
+
+/// Enables a peripheral interrupt in the NVIC, by IRQ number (the vector
+/// table index is `16 + irq`).
+pub unsafe fn enable_irq(irq: u8) {
+    let iser = (0xE000E100 + 4 * (irq as u32 / 32)) as *mut u32;
+    iser.write_volatile(1 << (irq % 32));
+}
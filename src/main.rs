@@ -1,4 +1,5 @@
 #![feature(stdsimd)]
+#![feature(asm)]
 #![no_std]
 #![no_main]
 
@@ -11,6 +12,10 @@ mod watchdog;
 mod mcg;
 mod osc;
 mod uart;
+mod time;
+mod interrupt;
+mod i2c;
+mod flash;
 
 #[no_mangle]
 pub extern fn main() {
@@ -27,40 +32,38 @@ pub extern fn main() {
     osc.enable(10);
     // Turn on the Port C clock gate
     sim.enable_clock(sim::Clock::PortC);
-    // Set our clocks:
-    // core: 72Mhz
-    // peripheral: 36MHz
-    // flash: 24MHz
-    sim.set_dividers(1, 2, 3);
-    // We would also set the USB divider here if we wanted to use it.
 
     // Now we can start setting up the MCG for our needs.
-    if let mcg::Clock::Fei(mut fei) = mcg.clock() {
+    let core_clock = if let mcg::Clock::Fei(mut fei) = mcg.clock() {
         // Our 16MHz xtal is "very fast", and needs to be divided
         // by 512 to be in the acceptable FLL range.
         fei.enable_xtal(mcg::OscRange::VeryHigh);
-        let fbe = fei.use_external(512);
+        let fbe = fei.use_external(512, time::Hertz::mhz(16));
 
         // PLL is 27/6 * xtal == 72MHz
         let pbe = fbe.enable_pll(27, 6);
-        pbe.use_pll();
+        pbe.use_pll()
     } else {
         panic!("Somehow the clock wasn't in FEI mode");
-    }
+    };
+
+    // Set our clocks:
+    // core: 72Mhz
+    // peripheral: 36MHz
+    // flash: 24MHz
+    sim.set_dividers(core_clock, 1, 2, 3);
+    // We would also set the USB divider here if we wanted to use it.
 
     let mut gpio = pin.make_gpio();
 
     gpio.output();
     gpio.high();
 
-    sim.enable_clock(sim::Clock::PortB);
-    sim.enable_clock(sim::Clock::Uart0);
+    let port_b = sim.port(port::PortName::B);
+    let rx = port_b.pin(16).make_rx();
+    let tx = port_b.pin(17).make_tx();
 
-    let uart = unsafe {
-        let rx = port::Port::new(port::PortName::B).pin(16).make_rx();
-        let tx = port::Port::new(port::PortName::B).pin(17).make_tx();
-        uart::Uart::new(0, Some(rx), Some(tx), (468, 24))
-    };
+    let uart = sim.uart(0, Some(rx), Some(tx), 9600);
 
     writeln!(uart, "Hello, World").unwrap();
 
@@ -89,11 +92,66 @@ extern {
     fn _stack_top();
 }
 
+unsafe extern fn default_handler() {
+    loop {}
+}
+
 #[link_section = ".vectors"]
 #[no_mangle]
-pub static _VECTORS: [unsafe extern fn(); 2] = [
+pub static _VECTORS: [unsafe extern fn(); 53] = [
     _stack_top,
     main,
+    default_handler, // NMI
+    default_handler, // HardFault
+    default_handler, // MemManage
+    default_handler, // BusFault
+    default_handler, // UsageFault
+    default_handler, // Reserved
+    default_handler, // Reserved
+    default_handler, // Reserved
+    default_handler, // Reserved
+    default_handler, // SVCall
+    default_handler, // DebugMonitor
+    default_handler, // Reserved
+    default_handler, // PendSV
+    default_handler, // SysTick
+    default_handler, // IRQ0: DMA channel 0
+    default_handler, // IRQ1: DMA channel 1
+    default_handler, // IRQ2: DMA channel 2
+    default_handler, // IRQ3: DMA channel 3
+    default_handler, // IRQ4: DMA channel 4
+    default_handler, // IRQ5: DMA channel 5
+    default_handler, // IRQ6: DMA channel 6
+    default_handler, // IRQ7: DMA channel 7
+    default_handler, // IRQ8: DMA channel 8
+    default_handler, // IRQ9: DMA channel 9
+    default_handler, // IRQ10: DMA channel 10
+    default_handler, // IRQ11: DMA channel 11
+    default_handler, // IRQ12: DMA channel 12
+    default_handler, // IRQ13: DMA channel 13
+    default_handler, // IRQ14: DMA channel 14
+    default_handler, // IRQ15: DMA channel 15
+    default_handler, // IRQ16: DMA error
+    default_handler, // IRQ17: MCM
+    default_handler, // IRQ18: FTFL command complete
+    default_handler, // IRQ19: FTFL read collision
+    default_handler, // IRQ20: Low-voltage detect/warning
+    default_handler, // IRQ21: LLWU
+    default_handler, // IRQ22: WDOG/EWM
+    default_handler, // IRQ23: I2C0
+    default_handler, // IRQ24: I2C1
+    default_handler, // IRQ25: SPI0
+    default_handler, // IRQ26: SPI1
+    default_handler, // IRQ27: CAN0 ORed message buffer
+    default_handler, // IRQ28: CAN0 bus off
+    default_handler, // IRQ29: CAN0 error
+    default_handler, // IRQ30: CAN0 Tx warning
+    default_handler, // IRQ31: CAN0 Rx warning
+    default_handler, // IRQ32: CAN0 wakeup
+    default_handler, // IRQ33: I2S0 Tx
+    default_handler, // IRQ34: I2S0 Rx
+    default_handler, // IRQ35: UART0 LON
+    uart::uart0_status_isr, // IRQ36: UART0 status
 ];
 
 #[link_section = ".flashconfig"]
@@ -3,6 +3,7 @@ use volatile::Volatile;
 use bit_field::BitField;
 
 use super::osc::OscToken;
+use super::time::Hertz;
 
 use core::sync::atomic::{AtomicBool,Ordering};
 
@@ -33,11 +34,13 @@ pub struct Fei { // FLL enabled, internal (reference)
 }
 
 pub struct Fbe { // FLL enabled, external
-    mcg: Mcg
+    mcg: Mcg,
+    xtal: Hertz
 }
 
 pub struct Pbe { // PLL enabled, external
-    mcg: Mcg
+    mcg: Mcg,
+    freq: Hertz
 }
 
 pub enum OscRange {
@@ -103,7 +106,7 @@ impl Fei {
         while !self.mcg.reg.s.read().get_bit(1) {}
     }
 
-    pub fn use_external(self, divide: u32) -> Fbe {
+    pub fn use_external(self, divide: u32, xtal: Hertz) -> Fbe {
         let osc = self.mcg.reg.c2.read().get_bits(4..6);
         let frdiv = if osc == OscRange::Low as u8 {
             match divide {
@@ -144,7 +147,7 @@ impl Fei {
         while self.mcg.reg.s.read().get_bit(4) {}
         while self.mcg.reg.s.read().get_bits(2..4) != OscSource::External as u8 {}
 
-        Fbe { mcg: self.mcg }
+        Fbe { mcg: self.mcg, xtal }
     }
 }
 
@@ -172,12 +175,19 @@ impl Fbe {
         // Wait for the PLL to be "locked" and stable
         while !self.mcg.reg.s.read().get_bit(6) {}
 
-        Pbe { mcg: self.mcg }
+        // PLL output is the reference crystal scaled by numerator/denominator.
+        // Multiply before dividing to avoid losing precision to truncation.
+        let freq = Hertz(self.xtal.0 * numerator as u32 / denominator as u32);
+
+        Pbe { mcg: self.mcg, freq }
     }
 }
 
 impl Pbe {
-    pub fn use_pll(self) {
+    /// Switches the core clock over to the PLL output and returns its
+    /// frequency, so callers can pass it on to `Sim::set_dividers` instead
+    /// of hand-computing it.
+    pub fn use_pll(self) -> Hertz {
         self.mcg.reg.c1.update(|c1| {
             c1.set_bits(6..8, OscSource::LockedLoop as u8);
         });
@@ -189,5 +199,7 @@ impl Pbe {
         // which would be invalid to set, we just check for the known
         // value "3" here.
         while self.mcg.reg.s.read().get_bits(2..4) != 3 {}
+
+        self.freq
     }
 }
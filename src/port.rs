@@ -1,13 +1,18 @@
 use core::cell::UnsafeCell;
+use core::convert::Infallible;
 use core::sync::atomic::{AtomicBool, Ordering};
 use crate::sim::ClockGate;
+use embedded_hal::digital::v2::{InputPin, OutputPin, StatefulOutputPin};
 use volatile::Volatile;
 use bit_field::BitField;
 
 #[derive(Clone,Copy)]
 pub enum PortName {
+    A,
+    B,
     C,
-    B
+    D,
+    E
 }
 
 #[repr(C,packed)]
@@ -21,6 +26,7 @@ struct PortRegs {
 
 pub struct Port {
     reg: UnsafeCell<&'static mut PortRegs>,
+    name: PortName,
     locks: [AtomicBool; 32],
     _gate: ClockGate,
 }
@@ -54,14 +60,35 @@ pub struct Rx<'a> {
     _pin: Pin<'a>
 }
 
+/// A pin's internal pull resistor configuration, set via `pcr` when the
+/// pin is used as an input.
+#[derive(Clone,Copy)]
+pub enum Pull {
+    Disabled,
+    Up,
+    Down
+}
+
+/// The edges that a pin can be configured to raise an interrupt on, via
+/// the IRQC field of `pcr`.
+#[derive(Clone,Copy)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Either
+}
+
 impl Port {
     pub unsafe fn new(name: PortName, gate: ClockGate) -> Port {
         let myself = &mut * match name {
+            PortName::A => 0x40049000 as *mut PortRegs,
+            PortName::B => 0x4004A000 as *mut PortRegs,
             PortName::C => 0x4004B000 as *mut PortRegs,
-            PortName::B => 0x4004A000 as *mut PortRegs
+            PortName::D => 0x4004C000 as *mut PortRegs,
+            PortName::E => 0x4004D000 as *mut PortRegs
         };
 
-        Port { reg: UnsafeCell::new(myself), locks: Default::default(), _gate: gate}
+        Port { reg: UnsafeCell::new(myself), name, locks: Default::default(), _gate: gate}
     }
 
     pub unsafe fn set_pin_mode(&self, p: usize, mode: u32) {
@@ -71,6 +98,47 @@ impl Port {
         });
     }
 
+    pub unsafe fn set_pull(&self, p: usize, pull: Pull) {
+        assert!(p < 32);
+        self.reg().pcr[p].update(|pcr| {
+            match pull {
+                Pull::Disabled => pcr.set_bit(1, false),
+                Pull::Up => { pcr.set_bit(1, true); pcr.set_bit(0, true); },
+                Pull::Down => { pcr.set_bit(1, true); pcr.set_bit(0, false); },
+            };
+        });
+    }
+
+    pub unsafe fn set_open_drain(&self, p: usize, enable: bool) {
+        assert!(p < 32);
+        self.reg().pcr[p].update(|pcr| {
+            pcr.set_bit(5, enable);
+        });
+    }
+
+    unsafe fn set_interrupt_config(&self, p: usize, irqc: u32) {
+        assert!(p < 32);
+        self.reg().pcr[p].update(|pcr| {
+            pcr.set_bits(16..20, irqc);
+        });
+    }
+
+    /// Returns whether `p` has a pending pin-change interrupt, per `isfr`.
+    pub fn check_interrupt(&self, p: usize) -> bool {
+        assert!(p < 32);
+        self.reg().isfr.read().get_bit(p)
+    }
+
+    /// Clears a pending pin-change interrupt on `p`. `isfr` bits are
+    /// write-1-to-clear, so writing anything else here would clear other
+    /// pins' pending flags too.
+    pub fn clear_interrupt(&self, p: usize) {
+        assert!(p < 32);
+        let mut cleared: u32 = 0;
+        cleared.set_bit(p, true);
+        self.reg().isfr.write(cleared);
+    }
+
     pub fn pin(&self, p: usize) -> Pin {
         assert!(p < 32);
         let was_init = self.locks[p].swap(true, Ordering::Relaxed);
@@ -86,12 +154,7 @@ impl Port {
     }
 
     pub fn name(&self) -> PortName {
-        let addr = (self as *const Port) as u32;
-        match addr {
-            0x4004B000 => PortName::C,
-            0x4004A000 => PortName::B,
-            _ => unreachable!()
-        }
+        self.name
     }
 
     fn reg(&self) -> &'static mut PortRegs {
@@ -147,11 +210,29 @@ impl <'a> Drop for Pin<'a> {
     }
 }
 
+impl<'a> Pin<'a> {
+    /// Configures this pin to raise a pin-change interrupt (see `isfr`) on
+    /// the given edge.
+    pub fn enable_interrupt(&self, edge: Edge) {
+        let irqc = match edge {
+            Edge::Rising => 0b1001,
+            Edge::Falling => 0b1010,
+            Edge::Either => 0b1011,
+        };
+        unsafe {
+            self.port.set_interrupt_config(self.pin, irqc);
+        }
+    }
+}
+
 impl<'a> Gpio<'a> {
     pub unsafe fn new(port: PortName, pin: Pin) -> Gpio {
         let gpio = match port {
+            PortName::A => 0x43FE0000 as *mut GpioBitband,
+            PortName::B => 0x43FE0800 as *mut GpioBitband,
             PortName::C => 0x43FE1000 as *mut GpioBitband,
-            PortName::B => 0x43FE0800 as *mut GpioBitband
+            PortName::D => 0x43FE1800 as *mut GpioBitband,
+            PortName::E => 0x43FE2000 as *mut GpioBitband
         };
 
         Gpio { gpio, pin }
@@ -163,6 +244,32 @@ impl<'a> Gpio<'a> {
         }
     }
 
+    pub fn input(&mut self, pull: Pull) {
+        unsafe {
+            (*self.gpio).pddr[self.pin.pin].write(0);
+            self.pin.port.set_pull(self.pin.pin, pull);
+        }
+    }
+
+    /// Enables or disables the pad's open-drain output mode, as used by
+    /// bit-banged buses (e.g. I2C) that need multiple drivers to share a
+    /// line without contention.
+    pub fn set_open_drain(&mut self, enable: bool) {
+        unsafe {
+            self.pin.port.set_open_drain(self.pin.pin, enable);
+        }
+    }
+
+    pub fn is_high(&self) -> bool {
+        unsafe {
+            (*self.gpio).pdir[self.pin.pin].read() != 0
+        }
+    }
+
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+
     pub fn high(&mut self) {
         unsafe {
             (*self.gpio).psor[self.pin.pin].write(1);
@@ -176,6 +283,44 @@ impl<'a> Gpio<'a> {
     }
 }
 
+impl<'a> OutputPin for Gpio<'a> {
+    type Error = Infallible;
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.high();
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.low();
+        Ok(())
+    }
+}
+
+impl<'a> StatefulOutputPin for Gpio<'a> {
+    fn is_set_high(&self) -> Result<bool, Infallible> {
+        unsafe {
+            Ok((*self.gpio).pdor[self.pin.pin].read() != 0)
+        }
+    }
+
+    fn is_set_low(&self) -> Result<bool, Infallible> {
+        Ok(!self.is_set_high()?)
+    }
+}
+
+impl<'a> InputPin for Gpio<'a> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Infallible> {
+        Ok(Gpio::is_high(self))
+    }
+
+    fn is_low(&self) -> Result<bool, Infallible> {
+        Ok(Gpio::is_low(self))
+    }
+}
+
 impl Rx<'_> {
     pub fn uart(&self) -> u8 {
         self.uart
@@ -3,6 +3,7 @@ use crate::port::Tx;
 use crate::uart::Uart;
 use crate::port::PortName;
 use crate::port::Port;
+use crate::time::Hertz;
 use volatile::Volatile;
 use bit_field::BitField;
 
@@ -37,7 +38,10 @@ struct SimRegs {
 }
 
 pub struct Sim {
-    reg: &'static mut SimRegs
+    reg: &'static mut SimRegs,
+    core_hz: Option<Hertz>,
+    bus_hz: Option<Hertz>,
+    flash_hz: Option<Hertz>
 }
 
 pub struct ClockGate {
@@ -73,10 +77,14 @@ impl Sim {
             panic!("Cannot initialize SIM: It's already active");
         }
         let reg = unsafe {&mut *(0x40047000 as *mut SimRegs)};
-        Sim {reg}
+        Sim {reg, core_hz: None, bus_hz: None, flash_hz: None}
     }
 
-    pub fn set_dividers(&mut self, core: u32, bus: u32, flash: u32) {
+    /// Sets the core/bus/flash clock dividers relative to `base` (the
+    /// frequency `Mcg` is currently running at, e.g. the value returned by
+    /// `Pbe::use_pll`), and records the resulting frequencies so they can
+    /// be used later for baud-rate and timing calculations.
+    pub fn set_dividers(&mut self, base: Hertz, core: u32, bus: u32, flash: u32) {
         let mut clkdiv: u32 = 0;
         clkdiv.set_bits(28..32, core-1);
         clkdiv.set_bits(24..28, bus-1);
@@ -84,12 +92,31 @@ impl Sim {
         unsafe {
             self.reg.clkdiv1.write(clkdiv);
         }
+
+        self.core_hz = Some(Hertz(base.0 / core));
+        self.bus_hz = Some(Hertz(base.0 / bus));
+        self.flash_hz = Some(Hertz(base.0 / flash));
+    }
+
+    pub fn core_clock(&self) -> Hertz {
+        self.core_hz.expect("set_dividers must be called before reading the core clock")
+    }
+
+    pub fn bus_clock(&self) -> Hertz {
+        self.bus_hz.expect("set_dividers must be called before reading the bus clock")
+    }
+
+    pub fn flash_clock(&self) -> Hertz {
+        self.flash_hz.expect("set_dividers must be called before reading the flash clock")
     }
 
     pub fn port(&mut self, port: PortName) -> Port {
         let gate = match port {
+            PortName::A => ClockGate::new(5, 9),
             PortName::B => ClockGate::new(5, 10),
             PortName::C => ClockGate::new(5, 11),
+            PortName::D => ClockGate::new(5, 12),
+            PortName::E => ClockGate::new(5, 13),
         };
         if gate.gate.read() != 0 {
             panic!("Cannot create Port instance; it is already in use");
@@ -100,7 +127,7 @@ impl Sim {
         }
     }
 
-    pub fn uart<'a, 'b>(&mut self, uart: u8, rx: Option<Rx<'a>>, tx: Option<Tx<'b>>, clkdiv: (u16, u8)) -> Uart<'a, 'b> {
+    pub fn uart<'a, 'b>(&mut self, uart: u8, rx: Option<Rx<'a>>, tx: Option<Tx<'b>>, baud: u32) -> Uart<'a, 'b> {
         let gate = match uart {
             0 => ClockGate::new(4, 10),
             _ => panic!("Cannot enable clock for UART {}", uart)
@@ -109,8 +136,11 @@ impl Sim {
             panic!("Cannot create Uart instance; it is already in use");
         }
         gate.gate.write(1);
+        // UART0 is clocked from the core clock on this part; other UARTs
+        // (once supported) would use the bus clock instead.
+        let module_clock = self.core_clock();
         unsafe {
-            Uart::new(uart, rx, tx, clkdiv, gate)
+            Uart::new(uart, rx, tx, module_clock, baud, gate)
         }
     }
 }
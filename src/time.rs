@@ -0,0 +1,27 @@
+/// A frequency, in hertz.
+///
+/// This exists so clock-tree and baud-rate math can't silently mix up units
+/// (MHz vs Hz, or a raw divisor vs an actual frequency) the way hand-tuned
+/// magic constants did before.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hertz(pub u32);
+
+impl Hertz {
+    pub fn hz(hz: u32) -> Hertz {
+        Hertz(hz)
+    }
+
+    pub fn khz(khz: u32) -> Hertz {
+        Hertz(khz * 1_000)
+    }
+
+    pub fn mhz(mhz: u32) -> Hertz {
+        Hertz(mhz * 1_000_000)
+    }
+}
+
+impl From<u32> for Hertz {
+    fn from(hz: u32) -> Hertz {
+        Hertz(hz)
+    }
+}
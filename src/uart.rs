@@ -0,0 +1,381 @@
+use core::cell::UnsafeCell;
+use core::convert::Infallible;
+use core::fmt;
+
+use crate::interrupt;
+use crate::port::{Rx as RxPin, Tx as TxPin};
+use crate::sim::ClockGate;
+use crate::time::Hertz;
+use embedded_hal::serial;
+use volatile::Volatile;
+use bit_field::BitField;
+
+const RX_BUFFER_CAPACITY: usize = 64;
+
+/// A fixed-capacity byte queue, filled from interrupt context and drained
+/// from `Rx::read`/`Rx::try_read`.
+struct RingBuffer {
+    buf: [u8; RX_BUFFER_CAPACITY],
+    head: usize,
+    len: usize,
+    dropped: u32,
+}
+
+impl RingBuffer {
+    const fn new() -> RingBuffer {
+        RingBuffer { buf: [0; RX_BUFFER_CAPACITY], head: 0, len: 0, dropped: 0 }
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.len == RX_BUFFER_CAPACITY {
+            // The ISR can't block waiting for the consumer to catch up, so
+            // the incoming byte is simply dropped and counted.
+            self.dropped = self.dropped.wrapping_add(1);
+            return;
+        }
+        self.buf[(self.head + self.len) % RX_BUFFER_CAPACITY] = byte;
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let byte = self.buf[self.head];
+        self.head = (self.head + 1) % RX_BUFFER_CAPACITY;
+        self.len -= 1;
+        Some(byte)
+    }
+}
+
+#[cfg(test)]
+mod ring_buffer_tests {
+    use super::{RingBuffer, RX_BUFFER_CAPACITY};
+
+    #[test]
+    fn pop_on_empty_returns_none() {
+        let mut rb = RingBuffer::new();
+        assert_eq!(rb.pop(), None);
+    }
+
+    #[test]
+    fn pushes_pop_back_out_in_fifo_order() {
+        let mut rb = RingBuffer::new();
+        rb.push(1);
+        rb.push(2);
+        rb.push(3);
+        assert_eq!(rb.pop(), Some(1));
+        assert_eq!(rb.pop(), Some(2));
+        assert_eq!(rb.pop(), Some(3));
+        assert_eq!(rb.pop(), None);
+    }
+
+    #[test]
+    fn wraps_around_the_backing_array() {
+        let mut rb = RingBuffer::new();
+        for i in 0..RX_BUFFER_CAPACITY {
+            rb.push(i as u8);
+        }
+        for _ in 0..(RX_BUFFER_CAPACITY / 2) {
+            rb.pop();
+        }
+        for i in 0..(RX_BUFFER_CAPACITY / 2) {
+            rb.push(100 + i as u8);
+        }
+        for i in (RX_BUFFER_CAPACITY / 2)..RX_BUFFER_CAPACITY {
+            assert_eq!(rb.pop(), Some(i as u8));
+        }
+        for i in 0..(RX_BUFFER_CAPACITY / 2) {
+            assert_eq!(rb.pop(), Some(100 + i as u8));
+        }
+        assert_eq!(rb.pop(), None);
+    }
+
+    #[test]
+    fn overflow_drops_and_counts_bytes_without_disturbing_whats_queued() {
+        let mut rb = RingBuffer::new();
+        for i in 0..RX_BUFFER_CAPACITY {
+            rb.push(i as u8);
+        }
+        assert_eq!(rb.dropped, 0);
+        rb.push(0xFF);
+        rb.push(0xFF);
+        assert_eq!(rb.dropped, 2);
+        for i in 0..RX_BUFFER_CAPACITY {
+            assert_eq!(rb.pop(), Some(i as u8));
+        }
+        assert_eq!(rb.pop(), None);
+    }
+}
+
+struct RxBuffer(UnsafeCell<RingBuffer>);
+
+// Access is only ever done with interrupts disabled, via `interrupt::free`,
+// in both the ISR and the consumer, so this is safe despite being shared
+// between an interrupt handler and `main`.
+unsafe impl Sync for RxBuffer {}
+
+static UART0_RX_BUFFER: RxBuffer = RxBuffer(UnsafeCell::new(RingBuffer::new()));
+
+/// UART0's status/error IRQ number, for `interrupt::enable_irq`. The vector
+/// table index is `16 + irq`.
+///
+/// Per the MK20DX256 vector table (PJRC's `teensy3` core, `kinetis.h`:
+/// `IRQ_UART0_STATUS`), this part's NVIC numbering runs DMA0-15 (0-15), DMA
+/// error (16), MCM/FTFL/low-voltage/LLWU/WDOG (17-22), I2C0/1 (23-24), SPI0/1
+/// (25-26), CAN0 (27-32), I2S0 (33-34), then UART0 LON/status/error (35-37)
+/// — landing UART0 status at 36, not 12.
+const UART0_STATUS_IRQ: u8 = 36;
+
+#[repr(C,packed)]
+struct UartRegs {
+    bdh: Volatile<u8>,
+    bdl: Volatile<u8>,
+    c1: Volatile<u8>,
+    c2: Volatile<u8>,
+    s1: Volatile<u8>,
+    s2: Volatile<u8>,
+    c3: Volatile<u8>,
+    d: Volatile<u8>,
+    ma1: Volatile<u8>,
+    ma2: Volatile<u8>,
+    c4: Volatile<u8>,
+    c5: Volatile<u8>,
+}
+
+pub struct Uart<'a, 'b> {
+    reg: *mut UartRegs,
+    _rx: Option<RxPin<'a>>,
+    _tx: Option<TxPin<'b>>,
+    _gate: ClockGate,
+}
+
+/// The transmit half of a split `Uart`.
+pub struct Tx<'b> {
+    reg: *mut UartRegs,
+    _pin: TxPin<'b>,
+    _gate: Option<ClockGate>,
+}
+
+/// The receive half of a split `Uart`.
+pub struct Rx<'a> {
+    reg: *mut UartRegs,
+    _pin: RxPin<'a>,
+    _gate: Option<ClockGate>,
+}
+
+impl<'a, 'b> Uart<'a, 'b> {
+    pub unsafe fn new(uart: u8, rx: Option<RxPin<'a>>, tx: Option<TxPin<'b>>, module_clock: Hertz, baud: u32, gate: ClockGate) -> Uart<'a, 'b> {
+        let reg = match uart {
+            0 => 0x4006A000 as *mut UartRegs,
+            _ => panic!("Uart {} does not exist", uart)
+        };
+
+        let (sbr, brfa) = divisor(module_clock, baud);
+
+        (*reg).bdh.update(|bdh| {
+            bdh.set_bits(0..5, sbr.get_bits(8..13) as u8);
+        });
+        (*reg).bdl.write(sbr.get_bits(0..8) as u8);
+        (*reg).c4.update(|c4| {
+            c4.set_bits(0..5, brfa);
+        });
+
+        (*reg).c2.update(|c2| {
+            c2.set_bit(2, rx.is_some());
+            c2.set_bit(3, tx.is_some());
+        });
+
+        Uart { reg, _rx: rx, _tx: tx, _gate: gate }
+    }
+
+    /// Splits this `Uart` into independent transmit and receive halves, one
+    /// per configured pin, so each can be moved into its own driver (or
+    /// handed off to an interrupt handler) without fighting the other for
+    /// ownership.
+    ///
+    /// The clock gate moves into `Rx` when both halves are present, since
+    /// the interrupt-driven receive path (`Rx::enable_interrupt`) is what
+    /// most needs UART0 to stay clocked for as long as it's held; it falls
+    /// back to `Tx` if there's no `Rx` to take it. Either way, dropping
+    /// whichever half ended up holding it disables UART0's clock gate, so
+    /// don't drop the gate-owning half while the other is still in use.
+    pub fn split(self) -> (Option<Tx<'b>>, Option<Rx<'a>>) {
+        let reg = self.reg;
+        let gate = self._gate;
+
+        match (self._tx, self._rx) {
+            (Some(tx_pin), Some(rx_pin)) => (
+                Some(Tx { reg, _pin: tx_pin, _gate: None }),
+                Some(Rx { reg, _pin: rx_pin, _gate: Some(gate) }),
+            ),
+            (Some(tx_pin), None) => (
+                Some(Tx { reg, _pin: tx_pin, _gate: Some(gate) }),
+                None,
+            ),
+            (None, Some(rx_pin)) => (
+                None,
+                Some(Rx { reg, _pin: rx_pin, _gate: Some(gate) }),
+            ),
+            (None, None) => (None, None),
+        }
+    }
+}
+
+/// Computes the 13-bit SBR divisor and 5-bit BRFA fine adjust for a given
+/// module clock and target baud rate: `SBR = module_clock / (16 * baud)`,
+/// with the remainder captured in 1/32nds as `BRFA`.
+fn divisor(module_clock: Hertz, baud: u32) -> (u16, u8) {
+    let denom = 16 * baud;
+    let mut sbr = module_clock.0 / denom;
+    let remainder = module_clock.0 % denom;
+    // Round to the nearest 1/32nd rather than truncating.
+    let mut brfa = (32 * remainder + denom / 2) / denom;
+    if brfa == 32 {
+        sbr += 1;
+        brfa = 0;
+    }
+
+    if sbr == 0 || sbr >= (1 << 13) {
+        panic!("Baud rate {} is not reachable from a {}Hz module clock", baud, module_clock.0);
+    }
+
+    (sbr as u16, brfa as u8)
+}
+
+impl<'a, 'b> fmt::Write for Uart<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            unsafe {
+                while !(*self.reg).s1.read().get_bit(7) {}
+                (*self.reg).d.write(byte);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'b> serial::Write<u8> for Tx<'b> {
+    type Error = Infallible;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+        unsafe {
+            if !(*self.reg).s1.read().get_bit(7) {
+                return Err(nb::Error::WouldBlock);
+            }
+            (*self.reg).d.write(byte);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        unsafe {
+            if !(*self.reg).s1.read().get_bit(6) {
+                return Err(nb::Error::WouldBlock);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> serial::Read<u8> for Rx<'a> {
+    type Error = Infallible;
+
+    fn read(&mut self) -> nb::Result<u8, Infallible> {
+        unsafe {
+            if !(*self.reg).s1.read().get_bit(5) {
+                return Err(nb::Error::WouldBlock);
+            }
+            Ok((*self.reg).d.read())
+        }
+    }
+}
+
+impl<'a> Rx<'a> {
+    /// Switches this `Rx` over to interrupt-driven reception: enables the
+    /// UART's RX-full interrupt and the corresponding NVIC line, so bytes
+    /// are drained into a ring buffer by `uart0_status_isr` instead of
+    /// needing to be polled. Once this is called, use `read`/`try_read`
+    /// rather than the `embedded_hal::serial::Read` impl above, which polls
+    /// the data register directly and would race the ISR for bytes.
+    pub fn enable_interrupt(&mut self) {
+        unsafe {
+            (*self.reg).c2.update(|c2| {
+                c2.set_bit(5, true);
+            });
+            interrupt::enable_irq(UART0_STATUS_IRQ);
+        }
+    }
+
+    /// Blocks until a byte is available in the ring buffer.
+    pub fn read(&mut self) -> u8 {
+        loop {
+            if let Some(byte) = self.try_read() {
+                return byte;
+            }
+        }
+    }
+
+    /// Pops a byte from the ring buffer, if one is available, without
+    /// blocking.
+    pub fn try_read(&mut self) -> Option<u8> {
+        interrupt::free(|| unsafe { (*UART0_RX_BUFFER.0.get()).pop() })
+    }
+
+    /// The number of bytes dropped because the ring buffer was full when
+    /// they arrived, or lost to a hardware receiver overrun.
+    pub fn dropped_bytes(&self) -> u32 {
+        interrupt::free(|| unsafe { (*UART0_RX_BUFFER.0.get()).dropped })
+    }
+}
+
+/// UART0 status/error interrupt handler: drains a received byte into the
+/// ring buffer backing `Rx::read`/`try_read`, or clears an overrun and
+/// counts the dropped byte.
+#[no_mangle]
+pub unsafe extern fn uart0_status_isr() {
+    let reg = 0x4006A000 as *mut UartRegs;
+    let s1 = (*reg).s1.read();
+
+    if s1.get_bit(3) {
+        // OR (overrun) is cleared by reading S1 then D; the byte that
+        // caused it is lost regardless, so just count it as dropped.
+        let _ = (*reg).d.read();
+        interrupt::free(|| (*UART0_RX_BUFFER.0.get()).dropped += 1);
+    } else if s1.get_bit(5) {
+        let byte = (*reg).d.read();
+        interrupt::free(|| (*UART0_RX_BUFFER.0.get()).push(byte));
+    }
+}
+
+#[cfg(test)]
+mod divisor_tests {
+    use super::divisor;
+    use crate::time::Hertz;
+
+    #[test]
+    fn fractional_divisor_is_split_into_sbr_and_brfa() {
+        assert_eq!(divisor(Hertz::mhz(72), 9600), (468, 24));
+    }
+
+    #[test]
+    fn rounds_the_fractional_remainder_instead_of_truncating() {
+        // remainder/denom = 22/48 scales to a BRFA of 14.67, which should
+        // round to 15, not truncate to 14.
+        assert_eq!(divisor(Hertz::hz(4_822), 3), (100, 15));
+    }
+
+    #[test]
+    fn brfa_rounding_up_to_32_carries_into_sbr() {
+        // remainder/denom = 15_800_000/16_000_000 rounds to a BRFA of
+        // exactly 32, which must carry into SBR and reset BRFA to 0 rather
+        // than overflowing its 5-bit field.
+        assert_eq!(divisor(Hertz::hz(175_800_000), 1_000_000), (11, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_slow_a_baud_rate_panics() {
+        divisor(Hertz::hz(1_000_000), 1);
+    }
+}